@@ -0,0 +1,179 @@
+use std::cmp::min;
+use std::fs::File;
+use std::io;
+use std::sync::Arc;
+
+use crate::paging::Paging;
+use crate::{BufferPolicy, OffsetRead};
+
+const DEFAULT_BUF_SIZE: usize = 8 * 1024;
+
+/// Async counterpart of [`OffsetRead`](crate::OffsetRead): a positioned read
+/// that does not block the calling task.
+pub trait OffsetReadAsync {
+    fn read_at_async(
+        &self,
+        buf: &mut [u8],
+        offset: u64,
+    ) -> impl std::future::Future<Output = io::Result<usize>> + Send;
+}
+
+impl OffsetReadAsync for &[u8] {
+    async fn read_at_async(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        Ok(self.get(offset as usize..).map_or(0, |r| {
+            let n = min(r.len(), buf.len());
+            buf[..n].copy_from_slice(&r[..n]);
+            n
+        }))
+    }
+}
+
+impl OffsetReadAsync for Arc<File> {
+    /// Offloads the blocking positioned read (`pread`/`seek_read`) to the
+    /// blocking thread pool so the async executor is never parked on file I/O.
+    async fn read_at_async(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let file = Arc::clone(self);
+        let mut tmp = vec![0u8; buf.len()];
+        let (tmp, n) = tokio::task::spawn_blocking(move || {
+            let n = OffsetRead::read_at(&*file, &mut tmp, offset)?;
+            io::Result::Ok((tmp, n))
+        })
+        .await
+        .map_err(io::Error::other)??;
+        buf[..n].copy_from_slice(&tmp[..n]);
+        Ok(n)
+    }
+}
+
+/// Async buffered positioned reader.
+///
+/// Mirrors [`BufOffsetReader`](crate::BufOffsetReader) exactly, down to sharing
+/// the [`Paging`] core, but awaits the underlying positioned read on a miss
+/// instead of blocking. This lets async servers do random-access reads into
+/// large files without parking the executor.
+pub struct AsyncBufOffsetReader<R: OffsetReadAsync> {
+    inner: R,
+    paging: Paging,
+}
+
+impl<R: OffsetReadAsync> AsyncBufOffsetReader<R> {
+    /// Creates a new buffered reader with default buffer capacity (currently 8KB).
+    pub fn new(inner: R) -> AsyncBufOffsetReader<R> {
+        AsyncBufOffsetReader::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    pub fn with_capacity(cap: usize, inner: R) -> AsyncBufOffsetReader<R> {
+        AsyncBufOffsetReader::with_capacity_and_policy(cap, BufferPolicy::StartAtOffset, inner)
+    }
+
+    /// Creates a new buffered reader with the given capacity and buffering
+    /// policy. See [`BufferPolicy`](crate::BufferPolicy).
+    pub fn with_capacity_and_policy(
+        cap: usize,
+        policy: BufferPolicy,
+        inner: R,
+    ) -> AsyncBufOffsetReader<R> {
+        AsyncBufOffsetReader {
+            inner,
+            paging: Paging::with_capacity(cap, policy),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.paging.capacity()
+    }
+
+    pub fn policy(&self) -> BufferPolicy {
+        self.paging.policy
+    }
+
+    pub fn contains(&self, r: crate::range::Range) -> bool {
+        self.paging.contains(&r)
+    }
+
+    pub fn clear(&mut self) {
+        self.paging.clear();
+    }
+
+    pub async fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        if buf.len() > self.capacity() {
+            return self.inner.read_at_async(buf, offset).await;
+        }
+
+        // A span that fits in the buffer but straddles an aligned page boundary
+        // spills into the next page; loop so the read fills the whole span
+        // (short only at EOF), mirroring `BufOffsetReader::read_at`.
+        let mut total = 0;
+        while total < buf.len() {
+            let off = offset + total as u64;
+            if !self.paging.is_resident(off, 1) {
+                let page = self.paging.page_start(off);
+                let count = self
+                    .inner
+                    .read_at_async(&mut self.paging.buffer, page)
+                    .await?;
+                self.paging.commit(page, count);
+                if count == 0 {
+                    break;
+                }
+            }
+            let n = self.paging.copy_out(&mut buf[total..], off);
+            if n == 0 {
+                break;
+            }
+            total += n;
+        }
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::seq;
+    use crate::*;
+
+    #[tokio::test]
+    async fn async_buffered_read_at() -> io::Result<()> {
+        let v = seq(200);
+        let mut r = AsyncBufOffsetReader::with_capacity(64, &v[..]);
+
+        let mut tmp = vec![0; 4];
+        r.read_at(&mut tmp, 0).await?;
+        assert_eq!(&tmp, &[0, 1, 2, 3]);
+        assert!(r.contains(40..50));
+
+        r.read_at(&mut tmp, 65).await?;
+        assert_eq!(&tmp, &[65, 66, 67, 68]);
+
+        // Read past the end returns 0.
+        let n = r.read_at(&mut tmp, 200).await?;
+        assert_eq!(n, 0);
+
+        // Read larger than capacity bypasses the buffer.
+        let mut big = vec![0; 100];
+        let n = r.read_at(&mut big, 100).await?;
+        assert_eq!(n, 100);
+        assert_eq!(&big[0..3], &[100, 101, 102]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn page_aligned_fills_across_boundary() -> io::Result<()> {
+        let v = seq(256);
+
+        // A 4-byte read at offset 14 straddles the 16-byte page boundary; it
+        // must still fill the whole buffer, matching StartAtOffset.
+        let mut r =
+            AsyncBufOffsetReader::with_capacity_and_policy(16, BufferPolicy::PageAligned, &v[..]);
+        let mut tmp = [0; 4];
+        let n = r.read_at(&mut tmp, 14).await?;
+        assert_eq!(n, 4);
+        assert_eq!(&tmp, &[14, 15, 16, 17]);
+
+        // A straddling read at the very end is short only because of EOF.
+        let n = r.read_at(&mut tmp, 254).await?;
+        assert_eq!(n, 2);
+        assert_eq!(&tmp[..2], &[254, 255]);
+        Ok(())
+    }
+}
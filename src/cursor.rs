@@ -0,0 +1,227 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::OffsetRead;
+
+/// Adapts an [`OffsetRead`] source into a plain [`Read`] + [`Seek`] stream.
+///
+/// The cursor keeps its own `pos: u64`, independent of any OS file cursor, so
+/// an owned `File`, a shared `&File`, or an `Arc<File>` can be handed to code
+/// that expects a `Read`/`Seek` while still going through the thread-safe
+/// positioned `read_at` interface (the shared forms rely on the blanket
+/// `OffsetRead` impls for `&R`/`Arc<R>`).
+///
+/// Note that [`BufOffsetReader`](crate::BufOffsetReader) is an
+/// [`OffsetReadMut`](crate::OffsetReadMut), not an [`OffsetRead`], so it cannot
+/// be wrapped by this cursor; it does not implement `Read` itself either.
+///
+/// The optional `length` is used solely to resolve [`SeekFrom::End`]; it is
+/// never checked against the underlying source, and reads past the end simply
+/// return 0 like the positioned interface does.
+pub struct OffsetCursor<R: OffsetRead> {
+    inner: R,
+    pos: u64,
+    length: Option<u64>,
+}
+
+impl<R: OffsetRead> OffsetCursor<R> {
+    /// Creates a cursor positioned at offset 0 with no known length.
+    ///
+    /// Seeking relative to the end always fails on such a cursor; create it
+    /// with [`with_length`](OffsetCursor::with_length) to allow that.
+    pub fn new(inner: R) -> OffsetCursor<R> {
+        OffsetCursor {
+            inner,
+            pos: 0,
+            length: None,
+        }
+    }
+
+    /// Creates a cursor positioned at offset 0 with the given length, used
+    /// only to interpret [`SeekFrom::End`].
+    pub fn with_length(inner: R, length: u64) -> OffsetCursor<R> {
+        OffsetCursor {
+            inner,
+            pos: 0,
+            length: Some(length),
+        }
+    }
+
+    /// The current stream position.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Consumes the cursor, returning the wrapped source.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: OffsetRead> Read for OffsetCursor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read_at(buf, self.pos)?;
+        self.pos = self.pos.wrapping_add(n as u64);
+        Ok(n)
+    }
+}
+
+impl<R: OffsetRead> Seek for OffsetCursor<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = resolve_seek(self.pos, self.length, pos)?;
+        Ok(self.pos)
+    }
+}
+
+/// A windowed [`Read`] + [`Seek`] view of an [`OffsetRead`] source.
+///
+/// Exposes the half-open byte range `start..end` of the underlying source as
+/// an independent stream whose offset 0 maps to `start`. Reads are clamped to
+/// the window, so they return 0 once `end` is reached even if the source has
+/// more data.
+pub struct OffsetSliceCursor<R: OffsetRead> {
+    inner: R,
+    start: u64,
+    end: u64,
+    pos: u64,
+}
+
+impl<R: OffsetRead> OffsetSliceCursor<R> {
+    /// Creates a cursor over the half-open range `start..end` of `inner`,
+    /// positioned at the start of the window.
+    pub fn new(inner: R, start: u64, end: u64) -> OffsetSliceCursor<R> {
+        OffsetSliceCursor {
+            inner,
+            start,
+            end: end.max(start),
+            pos: 0,
+        }
+    }
+
+    /// The length of the window in bytes.
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// Returns `true` if the window is empty.
+    pub fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
+
+    /// The current position relative to the start of the window.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Consumes the cursor, returning the wrapped source.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: OffsetRead> Read for OffsetSliceCursor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len().saturating_sub(self.pos);
+        if remaining == 0 {
+            return Ok(0);
+        }
+        let want = (buf.len() as u64).min(remaining) as usize;
+        let n = self.inner.read_at(&mut buf[..want], self.start + self.pos)?;
+        self.pos = self.pos.wrapping_add(n as u64);
+        Ok(n)
+    }
+}
+
+impl<R: OffsetRead> Seek for OffsetSliceCursor<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = resolve_seek(self.pos, Some(self.len()), pos)?;
+        Ok(self.pos)
+    }
+}
+
+fn resolve_seek(cur: u64, length: Option<u64>, pos: SeekFrom) -> io::Result<u64> {
+    let (base, offset) = match pos {
+        SeekFrom::Start(n) => return Ok(n),
+        SeekFrom::Current(n) => (cur, n),
+        SeekFrom::End(n) => {
+            let end = length.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "cannot seek from end without a known length",
+                )
+            })?;
+            (end, n)
+        }
+    };
+    let new = if offset >= 0 {
+        base.checked_add(offset as u64)
+    } else {
+        base.checked_sub(offset.unsigned_abs())
+    };
+    new.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid seek to a negative or overflowing position",
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::seq;
+    use crate::*;
+    use std::io::{Read, Seek, SeekFrom};
+
+    #[test]
+    fn cursor_read_and_seek() -> io::Result<()> {
+        let v = seq(200);
+        let mut c = OffsetCursor::with_length(&v[..], v.len() as u64);
+
+        let mut tmp = [0; 4];
+        c.read_exact(&mut tmp)?;
+        assert_eq!(&tmp, &[0, 1, 2, 3]);
+        assert_eq!(c.position(), 4);
+
+        c.read_exact(&mut tmp)?;
+        assert_eq!(&tmp, &[4, 5, 6, 7]);
+
+        assert_eq!(c.seek(SeekFrom::Start(100))?, 100);
+        c.read_exact(&mut tmp)?;
+        assert_eq!(&tmp, &[100, 101, 102, 103]);
+
+        assert_eq!(c.seek(SeekFrom::End(-2))?, 198);
+        let n = c.read(&mut tmp)?;
+        assert_eq!(n, 2);
+        assert_eq!(&tmp[..2], &[198, 199]);
+
+        assert_eq!(c.seek(SeekFrom::Current(-4))?, 196);
+        Ok(())
+    }
+
+    #[test]
+    fn cursor_seek_from_end_without_length() {
+        let v = seq(10);
+        let mut c = OffsetCursor::new(&v[..]);
+        assert!(c.seek(SeekFrom::End(0)).is_err());
+    }
+
+    #[test]
+    fn slice_cursor_is_windowed() -> io::Result<()> {
+        let v = seq(200);
+        let mut c = OffsetSliceCursor::new(&v[..], 100, 110);
+        assert_eq!(c.len(), 10);
+
+        let mut tmp = vec![0; 4];
+        c.read_exact(&mut tmp)?;
+        assert_eq!(&tmp, &[100, 101, 102, 103]);
+
+        // Read clamps to the end of the window.
+        c.seek(SeekFrom::Start(8))?;
+        let n = c.read(&mut tmp)?;
+        assert_eq!(n, 2);
+        assert_eq!(&tmp[..2], &[108, 109]);
+
+        let n = c.read(&mut tmp)?;
+        assert_eq!(n, 0);
+        Ok(())
+    }
+}
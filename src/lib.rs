@@ -1,4 +1,4 @@
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
 //! `BufOffsetReader` is like `std::io::BufReader`,
 //! but it allows reading at arbitrary positions in the underlying file.
 //!
@@ -31,11 +31,29 @@
 
 use std::cmp::min;
 use std::fs::File;
-use std::io;
-
+use std::io::{self, IoSlice, IoSliceMut};
+use std::sync::Arc;
+
+#[cfg(feature = "async")]
+mod r#async;
+mod cursor;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod paging;
 mod range;
+#[cfg(test)]
+mod test_util;
+mod writer;
+use paging::Paging;
 use range::*;
 
+#[cfg(feature = "async")]
+pub use r#async::{AsyncBufOffsetReader, OffsetReadAsync};
+pub use cursor::{OffsetCursor, OffsetSliceCursor};
+#[cfg(feature = "mmap")]
+pub use mmap::MmapOffsetReader;
+pub use writer::{BufOffsetReaderWriter, BufOffsetWriter};
+
 const DEFAULT_BUF_SIZE: usize = 8 * 1024;
 
 pub trait OffsetRead {
@@ -46,10 +64,35 @@ pub trait OffsetReadMut {
     fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
 }
 
+/// Scatter positioned read: fill `bufs` in order from consecutive bytes
+/// starting at `offset`, like `preadv`.
+pub trait OffsetReadVectored {
+    fn read_at_vectored(&self, bufs: &mut [IoSliceMut<'_>], offset: u64) -> io::Result<usize>;
+}
+
+/// `&mut self` variant of [`OffsetReadVectored`], for readers that maintain
+/// internal buffer state.
+pub trait OffsetReadVectoredMut {
+    fn read_at_vectored(&mut self, bufs: &mut [IoSliceMut<'_>], offset: u64) -> io::Result<usize>;
+}
+
+/// Controls which range of the underlying source is loaded into the buffer on
+/// a miss.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferPolicy {
+    /// Load `capacity` bytes starting exactly at the requested offset. Good for
+    /// generally "forward" reads, but every backward step re-reads the source.
+    StartAtOffset,
+    /// Round the requested offset down to a multiple of `capacity` and load the
+    /// aligned page `page..page+capacity`. Any offset inside that page is then a
+    /// buffer hit regardless of direction, so backward scans re-read the source
+    /// at most once per page.
+    PageAligned,
+}
+
 pub struct BufOffsetReader<R: OffsetRead> {
     inner: R,
-    range: Range,
-    buffer: Vec<u8>,
+    paging: Paging,
 }
 
 impl<R: OffsetRead> BufOffsetReader<R> {
@@ -59,39 +102,54 @@ impl<R: OffsetRead> BufOffsetReader<R> {
     }
 
     pub fn with_capacity(cap: usize, inner: R) -> BufOffsetReader<R> {
+        BufOffsetReader::with_capacity_and_policy(cap, BufferPolicy::StartAtOffset, inner)
+    }
+
+    /// Creates a new buffered reader with the given capacity and buffering
+    /// policy. See [`BufferPolicy`].
+    pub fn with_capacity_and_policy(
+        cap: usize,
+        policy: BufferPolicy,
+        inner: R,
+    ) -> BufOffsetReader<R> {
         BufOffsetReader {
             inner,
-            range: 0..0,
-            buffer: vec![0; cap],
+            paging: Paging::with_capacity(cap, policy),
         }
     }
 
+    /// The buffering policy in effect. See [`BufferPolicy`].
+    pub fn policy(&self) -> BufferPolicy {
+        self.paging.policy
+    }
+
     pub fn capacity(&self) -> usize {
-        self.buffer.len()
+        self.paging.capacity()
     }
 
     /// Check whether the specified data range (of the underlying file) is
     /// currently contained in the reader's in-memory buffer.
     pub fn contains(&self, r: Range) -> bool {
-        self.range.intersect(&r) == r
+        self.paging.contains(&r)
     }
 
     pub fn clear(&mut self) {
-        self.range = 0..0;
+        self.paging.clear();
     }
 
+    /// Read a page covering `offset` into the buffer.
     fn load_page_at_offset(&mut self, offset: u64) -> io::Result<usize> {
-        let count = self.inner.read_at(&mut self.buffer, offset)?;
-        self.range = (offset as usize)..(offset as usize + count);
+        let page = self.paging.page_start(offset);
+        let count = self.inner.read_at(&mut self.paging.buffer, page)?;
+        self.paging.commit(page, count);
         Ok(count)
     }
 
-    fn copy_range_to_slice(&self, r: &Range, buf: &mut [u8]) {
-        if r.len() > 0 {
-            let src = r.shift_left(self.range.start);
-            let dst = r.shift_left(r.start);
-            buf[dst].copy_from_slice(&self.buffer[src]);
-        }
+    /// Patch the resident buffer with bytes that were just written to the
+    /// underlying source at `offset`, keeping the buffer coherent with the
+    /// source. Bytes outside the currently loaded range are ignored.
+    pub(crate) fn patch_written(&mut self, offset: u64, data: &[u8]) {
+        self.paging.patch_written(offset, data);
     }
 }
 
@@ -101,19 +159,26 @@ impl<R: OffsetRead> OffsetReadMut for BufOffsetReader<R> {
             return self.inner.read_at(&mut buf, offset);
         }
 
-        let r = (offset as usize)..(offset as usize + buf.len());
-        let mut i = self.range.intersect(&r);
-
-        if i.len() < buf.len() {
-            self.load_page_at_offset(offset)?;
-            i = self.range.intersect(&r)
+        // A span that fits in the buffer but straddles an aligned page boundary
+        // spills into the next page; loop so the read fills the whole span
+        // (short only at EOF), matching `StartAtOffset` and the vectored path.
+        let mut total = 0;
+        while total < buf.len() {
+            let off = offset + total as u64;
+            if !self.paging.is_resident(off, 1) && self.load_page_at_offset(off)? == 0 {
+                break;
+            }
+            let n = self.paging.copy_out(&mut buf[total..], off);
+            if n == 0 {
+                break;
+            }
+            total += n;
         }
-        self.copy_range_to_slice(&i, &mut buf);
-        Ok(i.len())
+        Ok(total)
     }
 }
 
-impl OffsetRead for &[u8] {
+impl OffsetRead for [u8] {
     fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
         Ok(self.get(offset as usize..).map_or(0, |r| {
             let n = min(r.len(), buf.len());
@@ -123,6 +188,22 @@ impl OffsetRead for &[u8] {
     }
 }
 
+/// Shared references are positioned readers too, so a `&File` (or `&[u8]`) can
+/// be handed to code expecting an [`OffsetRead`] without giving up ownership.
+impl<R: OffsetRead + ?Sized> OffsetRead for &R {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+}
+
+/// An `Arc<File>` can be shared across threads and still serve positioned
+/// reads, since `read_at` takes `&self`.
+impl<R: OffsetRead + ?Sized> OffsetRead for Arc<R> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+}
+
 impl OffsetRead for File {
     /// Uses `std::os::unix::fs::FileExt::read_at()` (aka `pread()`) on unix
     /// and `std::os::windows::fs::FileExt::seek_read()` on windows.
@@ -139,10 +220,84 @@ impl OffsetRead for File {
     }
 }
 
+/// Portable `preadv` fallback: loop over `bufs`, reading each from the next
+/// offset and stopping at the first short read.
+fn read_at_vectored_looped<R: OffsetRead + ?Sized>(
+    src: &R,
+    bufs: &mut [IoSliceMut<'_>],
+    offset: u64,
+) -> io::Result<usize> {
+    let mut total = 0;
+    let mut off = offset;
+    for b in bufs.iter_mut() {
+        let n = src.read_at(&mut b[..], off)?;
+        total += n;
+        off += n as u64;
+        if n < b.len() {
+            break;
+        }
+    }
+    Ok(total)
+}
+
+impl OffsetReadVectored for File {
+    /// Positioned scatter read. There is no stable positioned `preadv` in
+    /// `std`, and this crate uses no `unsafe` outside the optional `mmap`
+    /// feature, so this loops `read_at` at
+    /// advancing offsets rather than issuing a single `preadv` syscall.
+    fn read_at_vectored(&self, bufs: &mut [IoSliceMut<'_>], offset: u64) -> io::Result<usize> {
+        read_at_vectored_looped(self, bufs, offset)
+    }
+}
+
+impl OffsetReadVectored for &[u8] {
+    fn read_at_vectored(&self, bufs: &mut [IoSliceMut<'_>], offset: u64) -> io::Result<usize> {
+        read_at_vectored_looped(self, bufs, offset)
+    }
+}
+
+impl<R: OffsetRead> OffsetReadVectoredMut for BufOffsetReader<R> {
+    /// Satisfies the whole requested span from the in-memory buffer when it is
+    /// resident, loading a page on a miss; if the loaded page still does not
+    /// cover the span, falls back to per-slice reads from the inner reader.
+    fn read_at_vectored(&mut self, bufs: &mut [IoSliceMut<'_>], offset: u64) -> io::Result<usize> {
+        let total: usize = bufs.iter().map(|b| b.len()).sum();
+        if total > self.capacity() {
+            return read_at_vectored_looped(&self.inner, bufs, offset);
+        }
+
+        if !self.paging.is_resident(offset, total) {
+            self.load_page_at_offset(offset)?;
+            if !self.paging.is_resident(offset, total) {
+                // The loaded page doesn't cover the full span (EOF or a page
+                // boundary); fall back to per-slice reads.
+                return read_at_vectored_looped(&self.inner, bufs, offset);
+            }
+        }
+
+        let mut off = offset;
+        for b in bufs.iter_mut() {
+            let n = self.paging.copy_out(&mut b[..], off);
+            off += n as u64;
+        }
+        Ok(total)
+    }
+}
+
 pub trait OffsetWrite {
     fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize>;
 }
 
+/// Gather positioned write: write the concatenation of `bufs` starting at
+/// `offset`, like `pwritev`.
+pub trait OffsetWriteVectored {
+    fn write_at_vectored(&self, bufs: &[IoSlice<'_>], offset: u64) -> io::Result<usize>;
+}
+
+pub trait OffsetWriteMut {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize>;
+}
+
 impl OffsetWrite for File {
     /// For convenience, we also expose write_at (for File), because
     /// code that needs to read_at might want to write_at.
@@ -162,8 +317,30 @@ impl OffsetWrite for File {
     }
 }
 
+impl OffsetWriteVectored for File {
+    /// Positioned gather write. As with [`read_at_vectored`], there is no
+    /// stable positioned `pwritev` in `std`, so this loops `write_at` at
+    /// advancing offsets rather than issuing a single `pwritev` syscall.
+    ///
+    /// [`read_at_vectored`]: OffsetReadVectored::read_at_vectored
+    fn write_at_vectored(&self, bufs: &[IoSlice<'_>], offset: u64) -> io::Result<usize> {
+        let mut total = 0;
+        let mut off = offset;
+        for b in bufs {
+            let n = self.write_at(&b[..], off)?;
+            total += n;
+            off += n as u64;
+            if n < b.len() {
+                break;
+            }
+        }
+        Ok(total)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::test_util::seq;
     use crate::*;
     use std::io::Write;
     use tempfile::tempfile;
@@ -295,4 +472,116 @@ mod tests {
         let mut reader = BufOffsetReader::with_capacity(64, file);
         do_reads(|b, o| reader.read_at(b, o));
     }
+
+    /// Counts how many times the underlying source is actually read.
+    struct Counting<'a> {
+        inner: &'a [u8],
+        reads: std::cell::Cell<usize>,
+    }
+
+    impl OffsetRead for Counting<'_> {
+        fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            self.reads.set(self.reads.get() + 1);
+            self.inner.read_at(buf, offset)
+        }
+    }
+
+    #[test]
+    fn page_aligned_descending_scan() -> Result<(), io::Error> {
+        let v = seq(256);
+
+        // With the default StartAtOffset policy every backward step misses.
+        let src = Counting {
+            inner: &v[..],
+            reads: std::cell::Cell::new(0),
+        };
+        let mut r = BufOffsetReader::with_capacity(16, src);
+        let mut tmp = [0; 4];
+        for o in (0..=200u64).rev().step_by(4) {
+            r.read_at(&mut tmp, o)?;
+            assert_eq!(tmp[0] as u64, o);
+        }
+        let start_at_offset = r.into_inner_reads();
+
+        // PageAligned reloads at most once per 16-byte page.
+        let src = Counting {
+            inner: &v[..],
+            reads: std::cell::Cell::new(0),
+        };
+        let mut r =
+            BufOffsetReader::with_capacity_and_policy(16, BufferPolicy::PageAligned, src);
+        for o in (0..=200u64).rev().step_by(4) {
+            r.read_at(&mut tmp, o)?;
+            assert_eq!(tmp[0] as u64, o);
+        }
+        let page_aligned = r.into_inner_reads();
+
+        assert!(
+            page_aligned < start_at_offset,
+            "page-aligned ({page_aligned}) should read less than start-at-offset ({start_at_offset})"
+        );
+        // Offsets 0..=200 touch aligned 16-byte pages 0..=12 (13 pages).
+        assert_eq!(page_aligned, 13);
+        Ok(())
+    }
+
+    impl BufOffsetReader<Counting<'_>> {
+        fn into_inner_reads(self) -> usize {
+            self.inner.reads.get()
+        }
+    }
+
+    #[test]
+    fn page_aligned_fills_across_boundary() -> Result<(), io::Error> {
+        let v = seq(256);
+
+        // A 4-byte read at offset 14 straddles the 16-byte page boundary; it
+        // must still fill the whole buffer, matching StartAtOffset.
+        let mut r =
+            BufOffsetReader::with_capacity_and_policy(16, BufferPolicy::PageAligned, &v[..]);
+        let mut tmp = [0; 4];
+        let n = r.read_at(&mut tmp, 14)?;
+        assert_eq!(n, 4);
+        assert_eq!(&tmp, &[14, 15, 16, 17]);
+
+        // A straddling read at the very end is short only because of EOF.
+        let n = r.read_at(&mut tmp, 254)?;
+        assert_eq!(n, 2);
+        assert_eq!(&tmp[..2], &[254, 255]);
+        Ok(())
+    }
+
+    #[test]
+    fn vectored_read_and_write() -> Result<(), io::Error> {
+        let v = seq(200);
+        let file = tempfile()?;
+
+        // Gather-write a header plus payload in one call.
+        let n = file.write_at_vectored(&[IoSlice::new(&[1, 2]), IoSlice::new(&v)], 0)?;
+        assert_eq!(n, 2 + v.len());
+
+        // Scatter-read the header and payload into separate buffers.
+        let mut head = [0; 2];
+        let mut body = [0; 4];
+        {
+            let mut bufs = [IoSliceMut::new(&mut head), IoSliceMut::new(&mut body)];
+            let n = file.read_at_vectored(&mut bufs, 0)?;
+            assert_eq!(n, 6);
+        }
+        assert_eq!(&head, &[1, 2]);
+        assert_eq!(&body, &[0, 1, 2, 3]);
+
+        // Same via the buffered reader, served from the resident page.
+        let mut r = BufOffsetReader::with_capacity(64, file);
+        let mut a = [0; 2];
+        let mut b = [0; 2];
+        {
+            let mut bufs = [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)];
+            let n = r.read_at_vectored(&mut bufs, 0)?;
+            assert_eq!(n, 4);
+        }
+        assert_eq!(&a, &[1, 2]);
+        assert_eq!(&b, &[0, 1]);
+        Ok(())
+    }
 }
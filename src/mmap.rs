@@ -0,0 +1,94 @@
+use std::cmp::min;
+use std::fs::File;
+use std::io;
+
+use memmap2::Mmap;
+
+use crate::OffsetRead;
+
+/// An [`OffsetRead`] source backed by a memory map of a [`File`].
+///
+/// The file is mapped once and every `read_at` is served as a plain memcpy out
+/// of the mapping, with no per-read syscall. Reads beyond the mapped length
+/// return 0, matching the slice and file implementations.
+///
+/// Because [`BufOffsetReader`](crate::BufOffsetReader) is generic over
+/// `R: OffsetRead`, an `MmapOffsetReader` can be used directly or wrapped in a
+/// `BufOffsetReader`. For a memory-mapped source the extra buffering layer is
+/// redundant, just as it gives no advantage over an in-memory `&[u8]`.
+pub struct MmapOffsetReader {
+    file: File,
+    map: Mmap,
+}
+
+impl MmapOffsetReader {
+    /// Memory-maps the whole file.
+    ///
+    /// Like all memory maps, the mapping assumes the underlying file is not
+    /// mutated or truncated by another process for the lifetime of the map;
+    /// doing so is undefined behavior.
+    pub fn new(file: File) -> io::Result<MmapOffsetReader> {
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(MmapOffsetReader { file, map })
+    }
+
+    /// The current mapped length in bytes.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the mapping is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Re-maps the file, picking up its current length. Call this after the
+    /// file has grown to make the new bytes visible through `read_at`.
+    pub fn refresh(&mut self) -> io::Result<()> {
+        self.map = unsafe { Mmap::map(&self.file)? };
+        Ok(())
+    }
+
+    /// A reference to the underlying file.
+    pub fn get_ref(&self) -> &File {
+        &self.file
+    }
+}
+
+impl OffsetRead for MmapOffsetReader {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        Ok(self.map.get(offset as usize..).map_or(0, |r| {
+            let n = min(r.len(), buf.len());
+            buf[..n].copy_from_slice(&r[..n]);
+            n
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::seq;
+    use crate::*;
+    use std::io::Write;
+    use tempfile::tempfile;
+
+    #[test]
+    fn mmap_read_at() -> io::Result<()> {
+        let v = seq(200);
+        let mut file = tempfile()?;
+        file.write_all(&v)?;
+
+        let m = MmapOffsetReader::new(file)?;
+        assert_eq!(m.len(), 200);
+
+        let mut tmp = [0; 4];
+        let n = m.read_at(&mut tmp, 100)?;
+        assert_eq!(n, 4);
+        assert_eq!(&tmp, &[100, 101, 102, 103]);
+
+        // Read past the end returns 0.
+        let n = m.read_at(&mut tmp, 200)?;
+        assert_eq!(n, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use crate::range::{Range, RangeExt};
+use crate::BufferPolicy;
+
+/// The buffer-management core shared by the sync and async buffered readers.
+///
+/// `Paging` owns the in-memory `buffer`, the `range` of the underlying source
+/// currently resident in it, and the [`BufferPolicy`]. It performs no I/O: the
+/// owner reads into [`buffer`](Paging::buffer) and then calls
+/// [`commit`](Paging::commit), which keeps the paging algorithm (range
+/// intersection, page alignment, copy-out) in a single place.
+pub(crate) struct Paging {
+    pub(crate) range: Range,
+    pub(crate) buffer: Vec<u8>,
+    pub(crate) policy: BufferPolicy,
+}
+
+impl Paging {
+    pub(crate) fn with_capacity(cap: usize, policy: BufferPolicy) -> Paging {
+        Paging {
+            range: 0..0,
+            buffer: vec![0; cap],
+            policy,
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub(crate) fn contains(&self, r: &Range) -> bool {
+        self.range.intersect(r) == *r
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.range = 0..0;
+    }
+
+    /// The offset at which a page covering `offset` should be loaded, per the
+    /// active policy.
+    pub(crate) fn page_start(&self, offset: u64) -> u64 {
+        match self.policy {
+            BufferPolicy::StartAtOffset => offset,
+            BufferPolicy::PageAligned => offset - (offset % self.capacity() as u64),
+        }
+    }
+
+    /// Record that `count` bytes were loaded into the buffer starting at `page`.
+    pub(crate) fn commit(&mut self, page: u64, count: usize) {
+        self.range = (page as usize)..(page as usize + count);
+    }
+
+    /// Whether the whole `offset..offset+len` span is currently resident.
+    pub(crate) fn is_resident(&self, offset: u64, len: usize) -> bool {
+        let r = (offset as usize)..(offset as usize + len);
+        self.range.intersect(&r).len() >= len
+    }
+
+    /// Copy the resident portion of `offset..offset+buf.len()` into `buf`,
+    /// returning the number of bytes copied.
+    pub(crate) fn copy_out(&self, buf: &mut [u8], offset: u64) -> usize {
+        let r = (offset as usize)..(offset as usize + buf.len());
+        let i = self.range.intersect(&r);
+        if !i.is_empty() {
+            let src = i.shift_left(self.range.start);
+            let dst = i.shift_left(r.start);
+            buf[dst].copy_from_slice(&self.buffer[src]);
+        }
+        i.len()
+    }
+
+    /// Patch the resident buffer with bytes written to the source at `offset`,
+    /// keeping the buffer coherent. Bytes outside the loaded range are ignored.
+    pub(crate) fn patch_written(&mut self, offset: u64, data: &[u8]) {
+        let w = (offset as usize)..(offset as usize + data.len());
+        let i = self.range.intersect(&w);
+        if !i.is_empty() {
+            let dst = i.shift_left(self.range.start);
+            let src = i.shift_left(offset as usize);
+            self.buffer[dst].copy_from_slice(&data[src]);
+        }
+    }
+}
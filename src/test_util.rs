@@ -0,0 +1,9 @@
+//! Test-only fixtures shared by the crate's unit tests.
+
+/// A byte sequence `0, 1, ..., n-1`, wrapping at 256. Used across tests as
+/// easy-to-verify fixture data, replacing the repeated
+/// `(0..n).into_iter().collect::<Vec<u8>>()` idiom (which also trips
+/// `clippy::useless_conversion`, since `Range` is already an `Iterator`).
+pub(crate) fn seq(n: usize) -> Vec<u8> {
+    (0..n).map(|i| (i % 256) as u8).collect()
+}
@@ -0,0 +1,244 @@
+use std::io;
+
+use crate::range::{Range, RangeExt};
+use crate::{BufOffsetReader, OffsetRead, OffsetWrite, OffsetWriteMut};
+
+const DEFAULT_BUF_SIZE: usize = 8 * 1024;
+
+/// Buffers positioned writes, coalescing sequential `write_at` calls into an
+/// in-memory buffer and flushing them to the underlying source in one call.
+///
+/// The buffer holds a single contiguous dirty [`Range`] of the source. A write
+/// that is contiguous with the buffered range extends it; a non-contiguous
+/// write, or one that would exceed the buffer capacity, flushes first. The
+/// buffer is also flushed on [`flush`](BufOffsetWriter::flush) and on `Drop`.
+pub struct BufOffsetWriter<W: OffsetWrite> {
+    inner: W,
+    start: u64,
+    buffer: Vec<u8>,
+    capacity: usize,
+}
+
+impl<W: OffsetWrite> BufOffsetWriter<W> {
+    /// Creates a new buffered writer with default buffer capacity (currently 8KB).
+    pub fn new(inner: W) -> BufOffsetWriter<W> {
+        BufOffsetWriter::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    pub fn with_capacity(cap: usize, inner: W) -> BufOffsetWriter<W> {
+        BufOffsetWriter {
+            inner,
+            start: 0,
+            buffer: Vec::with_capacity(cap),
+            capacity: cap,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The currently buffered (unflushed) range of the underlying source.
+    pub fn dirty_range(&self) -> Range {
+        (self.start as usize)..(self.start as usize + self.buffer.len())
+    }
+
+    /// Writes any buffered bytes to the underlying source.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let mut written = 0;
+        while written < self.buffer.len() {
+            let n = self
+                .inner
+                .write_at(&self.buffer[written..], self.start + written as u64)?;
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write buffered bytes",
+                ));
+            }
+            written += n;
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// A reference to the underlying source.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+}
+
+impl<W: OffsetWrite> OffsetWriteMut for BufOffsetWriter<W> {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        // Writes larger than the buffer bypass it entirely, after flushing so
+        // the source sees writes in order.
+        if buf.len() > self.capacity {
+            self.flush()?;
+            return self.inner.write_at(buf, offset);
+        }
+
+        let contiguous = !self.buffer.is_empty()
+            && offset == self.start + self.buffer.len() as u64;
+        if !contiguous || self.buffer.len() + buf.len() > self.capacity {
+            self.flush()?;
+        }
+        if self.buffer.is_empty() {
+            self.start = offset;
+        }
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+impl<W: OffsetWrite> Drop for BufOffsetWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Pairs a [`BufOffsetReader`] and a [`BufOffsetWriter`] over the same source.
+///
+/// `write_at` patches the reader's currently resident range in place (so a
+/// `read_at` already served from cache sees the new bytes immediately) and
+/// `read_at` flushes the writer first whenever the requested span overlaps
+/// the writer's unflushed [`dirty_range`](BufOffsetWriter::dirty_range), or
+/// is not already resident in the reader (so a page loaded from the source
+/// never predates a buffered write). Together these mean a `read_at` into a
+/// region you have written, flushed or not, always sees the write.
+pub struct BufOffsetReaderWriter<R: OffsetRead, W: OffsetWrite> {
+    reader: BufOffsetReader<R>,
+    writer: BufOffsetWriter<W>,
+}
+
+impl<R: OffsetRead, W: OffsetWrite> BufOffsetReaderWriter<R, W> {
+    /// Creates a coherent reader/writer pair from an existing buffered reader
+    /// and writer (typically over clones of the same file).
+    pub fn new(reader: BufOffsetReader<R>, writer: BufOffsetWriter<W>) -> Self {
+        BufOffsetReaderWriter { reader, writer }
+    }
+
+    /// Buffered positioned read. See [`BufOffsetReader::read_at`].
+    ///
+    /// Flushes the writer first if the requested span overlaps its unflushed
+    /// dirty range, or if the span is not resident and the read may load from
+    /// the source, so unflushed writes are never missed.
+    pub fn read_at(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        use crate::OffsetReadMut;
+        let span: Range = (offset as usize)..(offset as usize + buf.len());
+        let dirty = self.writer.dirty_range();
+        if !dirty.intersect(&span).empty() || (!dirty.empty() && !self.reader.contains(span)) {
+            self.writer.flush()?;
+        }
+        self.reader.read_at(buf, offset)
+    }
+
+    /// Buffered positioned write that also patches the reader's resident buffer
+    /// so it stays coherent with the source.
+    pub fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let n = self.writer.write_at(buf, offset)?;
+        self.reader.patch_written(offset, &buf[..n]);
+        Ok(n)
+    }
+
+    /// Flushes the writer's buffer to the underlying source.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::seq;
+    use crate::*;
+    use tempfile::tempfile;
+
+    #[test]
+    fn coalesces_sequential_writes() -> io::Result<()> {
+        let file = tempfile()?;
+        let mut w = BufOffsetWriter::with_capacity(64, file.try_clone()?);
+
+        w.write_at(&[0, 1, 2, 3], 0)?;
+        w.write_at(&[4, 5, 6, 7], 4)?;
+        assert_eq!(w.dirty_range(), 0..8);
+        // Still buffered: the file hasn't seen anything yet.
+        let mut tmp = [9; 8];
+        assert_eq!(file.read_at(&mut tmp, 0)?, 0);
+
+        w.flush()?;
+        assert_eq!(file.read_at(&mut tmp, 0)?, 8);
+        assert_eq!(&tmp, &[0, 1, 2, 3, 4, 5, 6, 7]);
+        Ok(())
+    }
+
+    #[test]
+    fn non_contiguous_write_flushes() -> io::Result<()> {
+        let file = tempfile()?;
+        let mut w = BufOffsetWriter::with_capacity(64, file.try_clone()?);
+
+        w.write_at(&[1, 2, 3, 4], 0)?;
+        w.write_at(&[5, 6, 7, 8], 100)?;
+        // The first run was flushed when the second, non-contiguous write arrived.
+        let mut tmp = [0; 4];
+        assert_eq!(file.read_at(&mut tmp, 0)?, 4);
+        assert_eq!(&tmp, &[1, 2, 3, 4]);
+        assert_eq!(w.dirty_range(), 100..104);
+        Ok(())
+    }
+
+    #[test]
+    fn flush_on_drop() -> io::Result<()> {
+        let file = tempfile()?;
+        {
+            let mut w = BufOffsetWriter::with_capacity(64, file.try_clone()?);
+            w.write_at(&[7; 4], 0)?;
+        }
+        let mut tmp = [0; 4];
+        assert_eq!(file.read_at(&mut tmp, 0)?, 4);
+        assert_eq!(&tmp, &[7, 7, 7, 7]);
+        Ok(())
+    }
+
+    #[test]
+    fn reader_writer_stays_coherent() -> io::Result<()> {
+        let v = seq(200);
+        let file = tempfile()?;
+        file.write_at(&v, 0)?;
+
+        let reader = BufOffsetReader::with_capacity(64, file.try_clone()?);
+        let writer = BufOffsetWriter::with_capacity(64, file.try_clone()?);
+        let mut rw = BufOffsetReaderWriter::new(reader, writer);
+
+        let mut tmp = [0; 4];
+        rw.read_at(&mut tmp, 0)?;
+        assert_eq!(&tmp, &[0, 1, 2, 3]);
+
+        // Overwrite inside the reader's resident range; no stale bytes.
+        rw.write_at(&[100, 100, 100, 100], 0)?;
+        rw.read_at(&mut tmp, 0)?;
+        assert_eq!(&tmp, &[100, 100, 100, 100]);
+        Ok(())
+    }
+
+    #[test]
+    fn write_then_read_with_no_prior_cache() -> io::Result<()> {
+        let v = seq(200);
+        let file = tempfile()?;
+        file.write_at(&v, 0)?;
+
+        let reader = BufOffsetReader::with_capacity(64, file.try_clone()?);
+        let writer = BufOffsetWriter::with_capacity(64, file.try_clone()?);
+        let mut rw = BufOffsetReaderWriter::new(reader, writer);
+
+        // No read has happened yet, so the reader's buffer is empty: a naive
+        // "patch only if resident" scheme would no-op here, and the following
+        // read would reload stale bytes straight from the file.
+        rw.write_at(&[100, 100, 100, 100], 0)?;
+        let mut tmp = [0; 4];
+        rw.read_at(&mut tmp, 0)?;
+        assert_eq!(&tmp, &[100, 100, 100, 100]);
+        Ok(())
+    }
+}